@@ -0,0 +1,434 @@
+//! Inspect an MDK SQLite database without writing Rust.
+//!
+//! Scope is limited to what the pinned MDK storage crate exposes publicly: opening a
+//! database (plaintext or with a raw 32-byte key file, as written by desktop pika),
+//! applying or dry-running its migrations, checking whether a key opens it, and
+//! listing/exporting groups and message transcripts. Reading the schema version,
+//! rekeying and compaction have no public MDK API and are not offered here.
+//!
+//! Opening a database through `MdkSqliteStorage` applies pending migrations, which
+//! writes to the file. Every command except `migrate` therefore works on a scratch
+//! copy of the database and its WAL, leaving the original untouched.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow, bail};
+use clap::{Parser, Subcommand};
+use mdk_core::MDK;
+use mdk_core::prelude::GroupId;
+use mdk_sqlite_storage::{EncryptionConfig, MdkSqliteStorage};
+use mdk_storage_traits::groups::Pagination;
+use mdk_storage_traits::messages::types::Message;
+use pika_marmot_runtime::sqlite_sidecar_path;
+use serde_json::json;
+use tempfile::TempDir;
+
+const EXPORT_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Parser)]
+#[command(name = "mdk-storage-cli")]
+#[command(about = "Inspect and export MDK SQLite databases")]
+#[command(
+    after_help = "All commands except `migrate` run against a scratch copy of the \
+database, so they never modify it. Stop the app or daemon using the database first so \
+the copy is consistent."
+)]
+struct Cli {
+    /// Path to the MDK SQLite database (e.g. mls/<pubkey>/mdk.sqlite3 or mdk.sqlite)
+    #[arg(long)]
+    db: PathBuf,
+
+    /// Raw 32-byte SQLCipher key file (desktop pika writes this next to the DB as mdk.key).
+    /// Omit for unencrypted databases.
+    #[arg(long)]
+    key_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print file sizes and a summary of every group
+    Inspect,
+
+    /// Check whether the database opens with the given key file and/or as plaintext
+    VerifyEncryption,
+
+    /// Check that pending migrations apply cleanly, without touching the database
+    VerifyMigrations,
+
+    /// Apply pending migrations to the database in place
+    Migrate,
+
+    /// Export a group's full message transcript as JSON, oldest first
+    Export {
+        /// Nostr group ID (hex)
+        #[arg(long)]
+        group: String,
+    },
+}
+
+/// A database path plus the key to open it with, if any.
+struct Target {
+    db: PathBuf,
+    key: Option<[u8; 32]>,
+}
+
+/// A scratch copy of a database; removed when dropped.
+struct ScratchCopy {
+    _dir: TempDir,
+    target: Target,
+}
+
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_target(false)
+        .without_time()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    // Opening a missing path would create a fresh database; never do that here.
+    if !cli.db.is_file() {
+        bail!("no database file at {}", cli.db.display());
+    }
+    let target = Target {
+        db: cli.db.clone(),
+        key: cli.key_file.as_deref().map(read_key_file).transpose()?,
+    };
+
+    let out = match &cli.cmd {
+        Command::Inspect => cmd_inspect(&target)?,
+        Command::VerifyEncryption => cmd_verify_encryption(&target)?,
+        Command::VerifyMigrations => cmd_verify_migrations(&target)?,
+        Command::Migrate => cmd_migrate(&target)?,
+        Command::Export { group } => cmd_export(&target, group, EXPORT_PAGE_SIZE)?,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&out).expect("json encode")
+    );
+    Ok(())
+}
+
+fn parse_key(bytes: &[u8]) -> anyhow::Result<[u8; 32]> {
+    bytes.try_into().map_err(|_| {
+        anyhow!(
+            "invalid key file length: expected 32 bytes, got {}",
+            bytes.len()
+        )
+    })
+}
+
+fn read_key_file(path: &Path) -> anyhow::Result<[u8; 32]> {
+    let bytes = std::fs::read(path).with_context(|| format!("read key file {}", path.display()))?;
+    parse_key(&bytes)
+}
+
+/// Copy the database and its WAL (which may hold uncheckpointed writes) into a temp
+/// dir. The SHM index is rebuilt from the WAL on open, so it is not copied.
+fn scratch_copy(target: &Target) -> anyhow::Result<ScratchCopy> {
+    let dir = tempfile::tempdir().context("create scratch dir")?;
+    let name = target
+        .db
+        .file_name()
+        .ok_or_else(|| anyhow!("db path has no file name: {}", target.db.display()))?;
+    let db = dir.path().join(name);
+    std::fs::copy(&target.db, &db)
+        .with_context(|| format!("copy {} to scratch dir", target.db.display()))?;
+    let wal = sqlite_sidecar_path(&target.db, "wal");
+    if wal.is_file() {
+        std::fs::copy(&wal, sqlite_sidecar_path(&db, "wal"))
+            .with_context(|| format!("copy {} to scratch dir", wal.display()))?;
+    }
+    Ok(ScratchCopy {
+        _dir: dir,
+        target: Target {
+            db,
+            key: target.key,
+        },
+    })
+}
+
+fn open_storage(target: &Target) -> Result<MdkSqliteStorage, mdk_sqlite_storage::error::Error> {
+    match target.key {
+        Some(key) => MdkSqliteStorage::new_with_key(&target.db, EncryptionConfig::new(key)),
+        None => MdkSqliteStorage::new_unencrypted(&target.db),
+    }
+}
+
+fn open(target: &Target) -> anyhow::Result<MDK<MdkSqliteStorage>> {
+    let storage = open_storage(target)
+        .with_context(|| format!("open mdk sqlite db: {}", target.db.display()))?;
+    Ok(MDK::new(storage))
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|m| m.len())
+}
+
+fn cmd_inspect(target: &Target) -> anyhow::Result<serde_json::Value> {
+    let scratch = scratch_copy(target)?;
+    let mdk = open(&scratch.target)?;
+    let groups = mdk.get_groups().context("get groups")?;
+    let mut out = Vec::with_capacity(groups.len());
+    for g in &groups {
+        let member_count = mdk
+            .get_members(&g.mls_group_id)
+            .map(|m| m.len())
+            .context("get members")?;
+        out.push(json!({
+            "nostr_group_id": hex::encode(g.nostr_group_id),
+            "mls_group_id": hex::encode(g.mls_group_id.as_slice()),
+            "name": g.name,
+            "epoch": g.epoch,
+            "member_count": member_count,
+        }));
+    }
+    Ok(json!({
+        "db": target.db.display().to_string(),
+        "opened_with_key": target.key.is_some(),
+        "db_bytes": file_size(&target.db),
+        "wal_bytes": file_size(&sqlite_sidecar_path(&target.db, "wal")),
+        "shm_bytes": file_size(&sqlite_sidecar_path(&target.db, "shm")),
+        "groups": out,
+    }))
+}
+
+fn cmd_verify_encryption(target: &Target) -> anyhow::Result<serde_json::Value> {
+    let scratch = scratch_copy(target)?;
+    let opens_with_key = scratch
+        .target
+        .key
+        .map(|_| match open_storage(&scratch.target) {
+            Ok(_) => json!({ "ok": true }),
+            Err(mdk_sqlite_storage::error::Error::WrongEncryptionKey) => {
+                json!({ "ok": false, "error": "wrong_encryption_key" })
+            }
+            Err(e) => json!({ "ok": false, "error": e.to_string() }),
+        });
+    let plaintext = Target {
+        db: scratch.target.db.clone(),
+        key: None,
+    };
+    let opens_as_plaintext = open_storage(&plaintext).is_ok();
+    Ok(json!({
+        "db": target.db.display().to_string(),
+        "opens_with_key": opens_with_key,
+        "opens_as_plaintext": opens_as_plaintext,
+    }))
+}
+
+fn cmd_verify_migrations(target: &Target) -> anyhow::Result<serde_json::Value> {
+    let scratch = scratch_copy(target)?;
+    let result = open_storage(&scratch.target);
+    Ok(json!({
+        "db": target.db.display().to_string(),
+        "ok": result.is_ok(),
+        "error": result.err().map(|e| e.to_string()),
+    }))
+}
+
+fn cmd_migrate(target: &Target) -> anyhow::Result<serde_json::Value> {
+    open_storage(target)
+        .with_context(|| format!("migrate mdk sqlite db: {}", target.db.display()))?;
+    Ok(json!({
+        "db": target.db.display().to_string(),
+        "ok": true,
+    }))
+}
+
+fn all_messages(
+    mdk: &MDK<MdkSqliteStorage>,
+    mls_group_id: &GroupId,
+    page_size: usize,
+) -> anyhow::Result<Vec<Message>> {
+    let mut out = Vec::new();
+    loop {
+        let page = mdk
+            .get_messages(
+                mls_group_id,
+                Some(Pagination::new(Some(page_size), Some(out.len()))),
+            )
+            .context("get messages")?;
+        let done = page.len() < page_size;
+        out.extend(page);
+        if done {
+            return Ok(out);
+        }
+    }
+}
+
+fn cmd_export(
+    target: &Target,
+    nostr_group_id_hex: &str,
+    page_size: usize,
+) -> anyhow::Result<serde_json::Value> {
+    let gid_bytes = hex::decode(nostr_group_id_hex.trim()).context("decode group id hex")?;
+    let scratch = scratch_copy(target)?;
+    let mdk = open(&scratch.target)?;
+    let group = mdk
+        .get_groups()
+        .context("get groups")?
+        .into_iter()
+        .find(|g| g.nostr_group_id.as_slice() == gid_bytes.as_slice())
+        .ok_or_else(|| anyhow!("no group with ID {nostr_group_id_hex}"))?;
+
+    let mut msgs = all_messages(&mdk, &group.mls_group_id, page_size)?;
+    msgs.sort_by_key(|m| m.created_at);
+    let out: Vec<serde_json::Value> = msgs
+        .iter()
+        .map(|m| {
+            json!({
+                "message_id": m.id.to_hex(),
+                "from_pubkey": m.pubkey.to_hex(),
+                "kind": m.kind.as_u16(),
+                "created_at": m.created_at.as_secs(),
+                "content": m.content,
+                "tags": m.tags.iter().map(|t| t.as_slice()).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Ok(json!({
+        "nostr_group_id": hex::encode(group.nostr_group_id),
+        "name": group.name,
+        "messages": out,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdk_core::prelude::NostrGroupConfigData;
+    use nostr_sdk::prelude::*;
+
+    /// Create the DB at `target` holding one solo group with a message per timestamp.
+    /// Returns the group's nostr group id (hex).
+    fn seed_db(target: &Target, timestamps: &[u64]) -> String {
+        let mdk = open(target).expect("open seed db");
+        let pubkey = Keys::generate().public_key();
+        let config = NostrGroupConfigData::new(
+            "Export".to_string(),
+            String::new(),
+            None,
+            None,
+            None,
+            vec![RelayUrl::parse("wss://test.relay").unwrap()],
+            vec![pubkey],
+        );
+        let result = mdk
+            .create_group(&pubkey, vec![], config)
+            .expect("create_group");
+        let group_id = result.group.mls_group_id.clone();
+        mdk.merge_pending_commit(&group_id)
+            .expect("merge_pending_commit");
+
+        for ts in timestamps {
+            let rumor = UnsignedEvent::new(
+                pubkey,
+                Timestamp::from(*ts),
+                Kind::ChatMessage,
+                [Tag::custom(TagKind::custom("seq"), vec![ts.to_string()])],
+                format!("message at {ts}"),
+            );
+            mdk.create_message(&group_id, rumor)
+                .expect("create_message");
+        }
+        hex::encode(result.group.nostr_group_id)
+    }
+
+    #[test]
+    fn parse_key_requires_32_bytes() {
+        assert_eq!(parse_key(&[7u8; 32]).expect("valid key"), [7u8; 32]);
+        let err = parse_key(&[0u8; 31]).expect_err("short key");
+        assert!(err.to_string().contains("got 31"));
+    }
+
+    #[test]
+    fn export_pages_through_all_messages_oldest_first() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let target = Target {
+            db: dir.path().join("mdk.sqlite"),
+            key: None,
+        };
+        let timestamps = [
+            1_700_000_300,
+            1_700_000_100,
+            1_700_000_500,
+            1_700_000_200,
+            1_700_000_400,
+        ];
+        let group = seed_db(&target, &timestamps);
+
+        // Page size 2 forces three pages, the last one short.
+        let out = cmd_export(&target, &group, 2).expect("export");
+        let created: Vec<u64> = out["messages"]
+            .as_array()
+            .expect("messages array")
+            .iter()
+            .map(|m| m["created_at"].as_u64().expect("created_at"))
+            .collect();
+        let mut expected = timestamps.to_vec();
+        expected.sort();
+        assert_eq!(created, expected);
+
+        // Exactly one full page still terminates and returns everything.
+        let out = cmd_export(&target, &group, timestamps.len()).expect("export");
+        assert_eq!(out["messages"].as_array().unwrap().len(), timestamps.len());
+    }
+
+    #[test]
+    fn read_only_commands_leave_the_database_untouched() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let target = Target {
+            db: dir.path().join("mdk.sqlite"),
+            key: None,
+        };
+        let group = seed_db(&target, &[1_700_000_000]);
+        let before = std::fs::read(&target.db).expect("read db");
+
+        cmd_inspect(&target).expect("inspect");
+        cmd_verify_encryption(&target).expect("verify encryption");
+        cmd_verify_migrations(&target).expect("verify migrations");
+        cmd_export(&target, &group, EXPORT_PAGE_SIZE).expect("export");
+
+        assert_eq!(std::fs::read(&target.db).expect("read db"), before);
+    }
+
+    #[test]
+    fn verify_encryption_reports_wrong_key_and_plaintext() {
+        let dir = tempfile::tempdir().expect("tempdir");
+
+        let encrypted = Target {
+            db: dir.path().join("encrypted.sqlite3"),
+            key: Some([1u8; 32]),
+        };
+        drop(open_storage(&encrypted).expect("create encrypted db"));
+
+        let out = cmd_verify_encryption(&encrypted).expect("verify");
+        assert_eq!(out["opens_with_key"]["ok"], true);
+        assert_eq!(out["opens_as_plaintext"], false);
+
+        let wrong_key = Target {
+            db: encrypted.db.clone(),
+            key: Some([2u8; 32]),
+        };
+        let out = cmd_verify_encryption(&wrong_key).expect("verify");
+        assert_eq!(out["opens_with_key"]["ok"], false);
+        assert_eq!(out["opens_with_key"]["error"], "wrong_encryption_key");
+
+        let plaintext = Target {
+            db: dir.path().join("plain.sqlite"),
+            key: None,
+        };
+        drop(open_storage(&plaintext).expect("create plaintext db"));
+        let out = cmd_verify_encryption(&plaintext).expect("verify");
+        assert!(out["opens_with_key"].is_null());
+        assert_eq!(out["opens_as_plaintext"], true);
+    }
+}
@@ -59,9 +59,40 @@ pub fn open_mdk(state_dir: &Path) -> Result<PikaMdk> {
     // Unencrypted for dev/test usage.
     let storage = MdkSqliteStorage::new_unencrypted(&db_path)
         .with_context(|| format!("open mdk sqlite: {}", db_path.display()))?;
+    restrict_sqlite_permissions(&db_path);
     Ok(MDK::new(storage))
 }
 
+/// SQLite names a database's sidecars `<db>-wal` / `<db>-shm`.
+pub fn sqlite_sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(format!("-{suffix}"));
+    PathBuf::from(path)
+}
+
+/// Best-effort: chmod a SQLite database and any existing WAL/SHM files to 0600.
+///
+/// The database file is what matters: SQLite creates new WAL/SHM files with the main
+/// file's mode, so once it is 0600 later sidecars follow. Sidecars are included only
+/// for files created before the chmod (e.g. by migrations during open).
+pub fn restrict_sqlite_permissions(db_path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for path in [
+            db_path.to_path_buf(),
+            sqlite_sidecar_path(db_path, "wal"),
+            sqlite_sidecar_path(db_path, "shm"),
+        ] {
+            if path.exists() {
+                let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = db_path;
+}
+
 pub fn new_mdk(state_dir: &Path, _label: &str) -> Result<PikaMdk> {
     open_mdk(state_dir)
 }
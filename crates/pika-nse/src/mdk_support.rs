@@ -63,8 +63,39 @@ pub fn open_mdk(data_dir: &str, pubkey: &PublicKey, keychain_group: &str) -> Res
 
     let storage = MdkSqliteStorage::new(&db_path, SERVICE_ID, &db_key_id(&pubkey_hex))
         .with_context(|| format!("open encrypted mdk sqlite db: {}", db_path.display()))?;
+    restrict_sqlite_permissions(&db_path);
 
     Ok(MDK::builder(storage)
         .with_config(MdkConfig::default())
         .build())
 }
+
+/// Best-effort: chmod the DB and any existing WAL/SHM files to 0600.
+///
+/// Same as `pika_marmot_runtime::restrict_sqlite_permissions`, copied so the extension
+/// doesn't pull in that crate's nostr-sdk/tokio deps; keep the two in sync. SQLite
+/// creates new sidecars with the main file's mode, so the DB chmod is what matters.
+fn restrict_sqlite_permissions(db_path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for path in [
+            db_path.to_path_buf(),
+            sqlite_sidecar_path(db_path, "wal"),
+            sqlite_sidecar_path(db_path, "shm"),
+        ] {
+            if path.exists() {
+                let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = db_path;
+}
+
+/// SQLite names a database's sidecars `<db>-wal` / `<db>-shm`.
+fn sqlite_sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(format!("-{suffix}"));
+    PathBuf::from(path)
+}
@@ -5,6 +5,7 @@ use anyhow::{anyhow, Context, Result};
 use mdk_core::{MdkConfig, MDK};
 use mdk_sqlite_storage::MdkSqliteStorage;
 use nostr_sdk::prelude::PublicKey;
+use pika_marmot_runtime::{restrict_sqlite_permissions, sqlite_sidecar_path};
 
 pub type PikaMdk = MDK<MdkSqliteStorage>;
 
@@ -72,7 +73,22 @@ fn init_keyring_inner(#[allow(unused)] keychain_group: &str) -> Result<()> {
     }
 }
 
+/// The DB file plus its WAL/SHM sidecars.
+fn mdk_db_artifact_paths(db_path: &Path) -> [PathBuf; 3] {
+    [
+        db_path.to_path_buf(),
+        sqlite_sidecar_path(db_path, "wal"),
+        sqlite_sidecar_path(db_path, "shm"),
+    ]
+}
+
 pub fn open_mdk(data_dir: &str, pubkey: &PublicKey, keychain_group: &str) -> Result<PikaMdk> {
+    let mdk = open_mdk_inner(data_dir, pubkey, keychain_group)?;
+    restrict_sqlite_permissions(&mdk_db_path(data_dir, &pubkey.to_hex()));
+    Ok(mdk)
+}
+
+fn open_mdk_inner(data_dir: &str, pubkey: &PublicKey, keychain_group: &str) -> Result<PikaMdk> {
     init_keyring_once(keychain_group)?;
 
     let pubkey_hex = pubkey.to_hex();
@@ -141,9 +157,9 @@ fn is_legacy_missing_file_key_error(err: &anyhow::Error) -> bool {
 
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 fn remove_mdk_db_artifacts(db_path: &Path) {
-    let _ = std::fs::remove_file(db_path);
-    let _ = std::fs::remove_file(db_path.with_extension("sqlite3-shm"));
-    let _ = std::fs::remove_file(db_path.with_extension("sqlite3-wal"));
+    for path in mdk_db_artifact_paths(db_path) {
+        let _ = std::fs::remove_file(path);
+    }
 }
 
 /// Desktop: file-based encryption key stored next to the DB file.
@@ -331,6 +347,73 @@ mod tests {
         drop(reopened);
     }
 
+    #[cfg(unix)]
+    fn assert_mode_0600(path: &Path) {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600, "{} should be 0600", path.display());
+    }
+
+    #[cfg(unix)]
+    fn create_solo_group(mdk: &PikaMdk, pubkey: &PublicKey) {
+        use mdk_core::prelude::NostrGroupConfigData;
+        use nostr_sdk::prelude::RelayUrl;
+
+        let config = NostrGroupConfigData::new(
+            "Test".to_string(),
+            String::new(),
+            None,
+            None,
+            None,
+            vec![RelayUrl::parse("wss://test.relay").unwrap()],
+            vec![*pubkey],
+        );
+        let result = mdk
+            .create_group(pubkey, vec![], config)
+            .expect("create_group");
+        mdk.merge_pending_commit(&result.group.mls_group_id)
+            .expect("merge_pending_commit");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn open_mdk_restricts_loose_db_and_sqlite_created_sidecars() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempdir().expect("tempdir");
+        let data_dir = tmp.path().to_string_lossy().to_string();
+        let pubkey = Keys::generate().public_key();
+        let db_path = mdk_db_path(&data_dir, &pubkey.to_hex());
+
+        // An existing DB left at 0644 (e.g. from a build before this hardening).
+        drop(open_mdk(&data_dir, &pubkey, "").expect("create db"));
+        std::fs::set_permissions(&db_path, std::fs::Permissions::from_mode(0o644))
+            .expect("loosen db permissions");
+
+        let mdk = open_mdk(&data_dir, &pubkey, "").expect("open");
+        for path in mdk_db_artifact_paths(&db_path) {
+            if path.exists() {
+                assert_mode_0600(&path);
+            }
+        }
+
+        create_solo_group(&mdk, &pubkey);
+        let wal_path = sqlite_sidecar_path(&db_path, "wal");
+        assert!(wal_path.exists(), "writes should go through the WAL");
+        assert_mode_0600(&wal_path);
+        drop(mdk);
+
+        // Sidecars SQLite creates later follow the DB's mode without another chmod.
+        let mdk = open_mdk_desktop_file_key(&data_dir, &pubkey).expect("reopen");
+        create_solo_group(&mdk, &pubkey);
+        assert!(wal_path.exists(), "writes should go through the WAL");
+        assert_mode_0600(&wal_path);
+        assert_mode_0600(&db_path);
+    }
+
     #[test]
     fn desktop_does_not_delete_db_on_non_legacy_open_failure() {
         let tmp = tempdir().expect("tempdir");